@@ -7,7 +7,7 @@ pub mod v3 {
     //!
     //! [spec]: https://spec.matrix.org/v1.4/client-server-api/#get_matrixmediav3thumbnailservernamemediaid
 
-    use http::header::CONTENT_TYPE;
+    use http::header::{ACCEPT, CONTENT_TYPE};
     use js_int::UInt;
     use ruma_common::{
         api::{request, response, Metadata},
@@ -16,7 +16,9 @@ pub mod v3 {
         IdParseError, MxcUri, OwnedServerName,
     };
 
-    use crate::{http_headers::CROSS_ORIGIN_RESOURCE_POLICY, PrivOwnedStr};
+    #[cfg(feature = "unstable-streaming-media")]
+    use crate::media::RawMediaBody;
+    use crate::{http_headers::CROSS_ORIGIN_RESOURCE_POLICY, media::Accept, PrivOwnedStr};
 
     const METADATA: Metadata = metadata! {
         method: GET,
@@ -78,15 +80,46 @@ pub mod v3 {
             rename = "fi.mau.msc2246.max_stall_ms"
         )]
         pub max_stall_ms: Option<UInt>,
+
+        /// The media types the client can decode, in order of preference,
+        /// with their `q` quality weights.
+        ///
+        /// A server implementation can use [`Accept::media_types`], which
+        /// returns the entries sorted by descending quality, to pick the
+        /// best encoding it supports.
+        #[ruma_api(header = ACCEPT)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub accept: Option<Accept>,
+
+        /// Whether an animated thumbnail is preferred, if the source
+        /// content is animated.
+        ///
+        /// Defaults to `false`.
+        #[ruma_api(query)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub animated: Option<bool>,
     }
 
     /// Response type for the `get_content_thumbnail` endpoint.
     #[response(error = crate::Error)]
     pub struct Response {
-        /// A thumbnail of the requested content.
+        /// A thumbnail of the requested content, buffered fully into memory.
         #[ruma_api(raw_body)]
+        #[cfg(not(feature = "unstable-streaming-media"))]
         pub file: Vec<u8>,
 
+        /// A thumbnail of the requested content.
+        ///
+        /// **Draft, not yet wired up**: with `unstable-streaming-media`
+        /// enabled, this field's type is [`RawMediaBody`] instead of
+        /// `Vec<u8>`, but `#[ruma_api(raw_body)]` doesn't know how to
+        /// (de)serialize it from/to an HTTP body yet — that needs a
+        /// corresponding `ruma_api` derive macro change that hasn't landed.
+        /// See [`RawMediaBody`]'s docs for details.
+        #[ruma_api(raw_body)]
+        #[cfg(feature = "unstable-streaming-media")]
+        pub file: RawMediaBody,
+
         /// The content type of the thumbnail.
         #[ruma_api(header = CONTENT_TYPE)]
         pub content_type: Option<String>,
@@ -118,6 +151,8 @@ pub mod v3 {
                 allow_remote: true,
                 #[cfg(feature = "unstable-msc2246")]
                 max_stall_ms: None,
+                accept: None,
+                animated: None,
             }
         }
 
@@ -134,6 +169,7 @@ pub mod v3 {
         /// Creates a new `Response` with the given thumbnail.
         ///
         /// The Cross-Origin Resource Policy defaults to `cross-origin`.
+        #[cfg(not(feature = "unstable-streaming-media"))]
         pub fn new(file: Vec<u8>) -> Self {
             Self {
                 file,
@@ -141,6 +177,19 @@ pub mod v3 {
                 cross_origin_resource_policy: Some("cross-origin".to_owned()),
             }
         }
+
+        /// Creates a new `Response` with the given thumbnail body, which may
+        /// be fully buffered or streamed in chunks.
+        ///
+        /// The Cross-Origin Resource Policy defaults to `cross-origin`.
+        #[cfg(feature = "unstable-streaming-media")]
+        pub fn new(file: RawMediaBody) -> Self {
+            Self {
+                file,
+                content_type: None,
+                cross_origin_resource_policy: Some("cross-origin".to_owned()),
+            }
+        }
     }
 
     /// The desired resizing method.
@@ -0,0 +1,267 @@
+//! Endpoints and types for the `/_matrix/media` namespace.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+pub mod get_content_thumbnail;
+
+/// A single entry of an HTTP `Accept` header: a media type together with its
+/// `q` quality weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcceptMediaType {
+    /// The media type, e.g. `image/webp`.
+    pub media_type: String,
+
+    /// The quality weight, in the range `0.0..=1.0`.
+    ///
+    /// Defaults to `1.0` if the `q` parameter was not present in the
+    /// header.
+    pub quality: f32,
+}
+
+impl AcceptMediaType {
+    /// Creates a new `AcceptMediaType` with the given media type and
+    /// quality weight.
+    pub fn new(media_type: impl Into<String>, quality: f32) -> Self {
+        Self { media_type: media_type.into(), quality }
+    }
+}
+
+/// The value of an HTTP `Accept` header, listing the media types a client
+/// can decode together with their `q` quality weights.
+///
+/// Entries are kept sorted by descending quality, so that a server
+/// implementation can pick the first entry it supports.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accept(Vec<AcceptMediaType>);
+
+impl Accept {
+    /// Creates a new `Accept` from the given media types, sorting them by
+    /// descending quality weight.
+    pub fn new(mut media_types: Vec<AcceptMediaType>) -> Self {
+        media_types.sort_by(|a, b| b.quality.partial_cmp(&a.quality).unwrap_or(Ordering::Equal));
+        Self(media_types)
+    }
+
+    /// The media types this `Accept` header lists, sorted by descending
+    /// quality weight.
+    pub fn media_types(&self) -> &[AcceptMediaType] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Accept {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered: Vec<String> = self
+            .0
+            .iter()
+            .map(|entry| {
+                if (entry.quality - 1.0).abs() < f32::EPSILON {
+                    entry.media_type.clone()
+                } else {
+                    format!("{};q={}", entry.media_type, entry.quality)
+                }
+            })
+            .collect();
+
+        write!(f, "{}", rendered.join(", "))
+    }
+}
+
+impl FromStr for Accept {
+    type Err = ParseAcceptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut media_types = Vec::new();
+
+        for entry in s.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let mut parts = entry.split(';');
+            let media_type = parts.next().unwrap().trim().to_owned();
+
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some(q) = param.trim().strip_prefix("q=") {
+                    quality = q.parse().map_err(|_| ParseAcceptError)?;
+                }
+            }
+
+            media_types.push(AcceptMediaType { media_type, quality });
+        }
+
+        Ok(Self::new(media_types))
+    }
+}
+
+/// An error encountered while parsing an `Accept` header.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid Accept header value")]
+pub struct ParseAcceptError;
+
+/// **Draft, not yet wired up.** The raw body of a media response.
+///
+/// This type and the `unstable-streaming-media` feature that gates it are a
+/// stub: nothing in `ruma_api`'s request/response (de)serialization knows
+/// about this type yet, so tagging a field `#[ruma_api(raw_body)]` with it
+/// does **not** make that endpoint stream over HTTP. Making that true needs
+/// a corresponding change in the `ruma_api` derive macro (so it emits code
+/// that drives [`RawMediaBody::into_stream`] / constructs from a streaming
+/// body instead of assuming `Vec<u8>`), which hasn't landed yet.
+///
+/// Until the macro side lands, treat this as scaffolding for that follow-up,
+/// not as a working streaming endpoint: [`RawMediaBody::into_stream`] and
+/// [`RawMediaBody::into_bytes`] are the calls that future macro-generated
+/// `IncomingResponse`/`OutgoingResponse` code would need to make.
+#[cfg(feature = "unstable-streaming-media")]
+pub enum RawMediaBody {
+    /// The whole body, already buffered in memory.
+    ///
+    /// This is the variant used when converting from the plain `Vec<u8>`
+    /// that non-streaming callers still construct responses with.
+    Bytes(Vec<u8>),
+
+    /// The body as a stream of chunks, so it can be forwarded without
+    /// buffering the whole file at once.
+    Stream(std::pin::Pin<Box<dyn futures_core::Stream<Item = bytes::Bytes> + Send>>),
+}
+
+#[cfg(feature = "unstable-streaming-media")]
+impl RawMediaBody {
+    /// Converts this body into a uniform stream of chunks, regardless of
+    /// whether it was constructed from a buffer or an existing stream.
+    ///
+    /// This is what an `OutgoingResponse` impl would drive to write the
+    /// body onto the wire in chunks instead of buffering it first.
+    pub fn into_stream(
+        self,
+    ) -> std::pin::Pin<Box<dyn futures_core::Stream<Item = bytes::Bytes> + Send>> {
+        match self {
+            Self::Bytes(bytes) => Box::pin(futures_util::stream::once(async move {
+                bytes::Bytes::from(bytes)
+            })),
+            Self::Stream(stream) => stream,
+        }
+    }
+
+    /// Buffers this body fully into memory, regardless of whether it was
+    /// constructed from a buffer or an existing stream.
+    ///
+    /// This is what an `IncomingResponse` impl would call for a caller that
+    /// wants the old `Vec<u8>` behavior back.
+    pub async fn into_bytes(self) -> Vec<u8> {
+        use futures_util::StreamExt;
+
+        match self {
+            Self::Bytes(bytes) => bytes,
+            Self::Stream(stream) => {
+                stream
+                    .fold(Vec::new(), |mut acc, chunk| async move {
+                        acc.extend_from_slice(&chunk);
+                        acc
+                    })
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(feature = "unstable-streaming-media")]
+impl std::fmt::Debug for RawMediaBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            Self::Stream(_) => f.debug_tuple("Stream").finish(),
+        }
+    }
+}
+
+#[cfg(feature = "unstable-streaming-media")]
+impl From<Vec<u8>> for RawMediaBody {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Accept, AcceptMediaType};
+
+    #[test]
+    fn parses_and_sorts_by_descending_quality() {
+        let accept: Accept =
+            "image/webp;q=0.9, image/avif;q=1.0, image/png;q=0.5".parse().unwrap();
+
+        assert_eq!(
+            accept.media_types(),
+            [
+                AcceptMediaType::new("image/avif", 1.0),
+                AcceptMediaType::new("image/webp", 0.9),
+                AcceptMediaType::new("image/png", 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_to_quality_one_without_q_param() {
+        let accept: Accept = "image/png".parse().unwrap();
+
+        assert_eq!(accept.media_types(), [AcceptMediaType::new("image/png", 1.0)]);
+    }
+
+    #[test]
+    fn display_omits_q_for_quality_one_and_round_trips() {
+        let accept = Accept::new(vec![
+            AcceptMediaType::new("image/avif", 1.0),
+            AcceptMediaType::new("image/webp", 0.9),
+        ]);
+
+        assert_eq!(accept.to_string(), "image/avif, image/webp;q=0.9");
+
+        let reparsed: Accept = accept.to_string().parse().unwrap();
+        assert_eq!(reparsed, accept);
+    }
+
+    #[test]
+    fn rejects_malformed_quality_value() {
+        let result: Result<Accept, _> = "image/webp;q=not-a-number".parse();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn empty_header_has_no_media_types() {
+        let accept: Accept = "".parse().unwrap();
+        assert!(accept.media_types().is_empty());
+    }
+}
+
+#[cfg(all(test, feature = "unstable-streaming-media"))]
+mod raw_media_body_tests {
+    use futures_util::stream;
+
+    use super::RawMediaBody;
+
+    #[test]
+    fn into_bytes_returns_the_buffer_unchanged() {
+        let body = RawMediaBody::Bytes(vec![1, 2, 3]);
+        let bytes = futures_executor::block_on(body.into_bytes());
+
+        assert_eq!(bytes, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn into_bytes_collects_a_stream() {
+        let chunks = vec![bytes::Bytes::from_static(b"ab"), bytes::Bytes::from_static(b"cd")];
+        let body = RawMediaBody::Stream(Box::pin(stream::iter(chunks)));
+        let bytes = futures_executor::block_on(body.into_bytes());
+
+        assert_eq!(bytes, b"abcd".to_vec());
+    }
+
+    #[test]
+    fn into_stream_yields_a_single_chunk_for_buffered_bodies() {
+        use futures_util::StreamExt;
+
+        let body = RawMediaBody::Bytes(vec![9, 9, 9]);
+        let chunks: Vec<_> = futures_executor::block_on(body.into_stream().collect());
+
+        assert_eq!(chunks, vec![bytes::Bytes::from_static(&[9, 9, 9])]);
+    }
+}
@@ -14,8 +14,10 @@ pub mod cancel;
 pub mod done;
 pub mod key;
 pub mod mac;
+pub mod qr_code;
 pub mod ready;
 pub mod request;
+pub mod sas;
 pub mod start;
 
 /// A hash algorithm.
@@ -0,0 +1,160 @@
+//! Helpers for computing and verifying the MACs carried by
+//! `m.key.verification.mac` events.
+
+#![cfg(feature = "crypto")]
+
+use base64::{
+    engine::general_purpose::{STANDARD, STANDARD_NO_PAD},
+    Engine,
+};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac as _};
+use sha2::Sha256;
+
+use super::MessageAuthenticationCode;
+
+/// An error that occurred while verifying a MAC.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MacError {
+    /// The calculated MAC does not match the MAC that was received.
+    #[error("calculated MAC does not match the received MAC")]
+    Mismatch,
+}
+
+/// The shared secret agreed upon during key verification, together with the
+/// MAC method that was negotiated for the verification flow.
+///
+/// Use [`Mac::calculate_mac`] and [`Mac::verify_mac`] to compute and check
+/// the MACs carried by `m.key.verification.mac` events, without having to
+/// hand-roll the HKDF/HMAC derivation described in the [spec].
+///
+/// [spec]: https://spec.matrix.org/v1.4/client-server-api/#mkeyverificationmac
+#[derive(Clone, Debug)]
+pub struct Mac {
+    shared_secret: Vec<u8>,
+    mac_method: MessageAuthenticationCode,
+}
+
+impl Mac {
+    /// Creates a new `Mac` from the agreed shared secret and the
+    /// [`MessageAuthenticationCode`] negotiated for the verification flow.
+    pub fn new(shared_secret: Vec<u8>, mac_method: MessageAuthenticationCode) -> Self {
+        Self { shared_secret, mac_method }
+    }
+
+    /// Calculates the MAC of `input`, deriving the per-MAC key via
+    /// HKDF-SHA256 with `info` as the info string.
+    ///
+    /// `info` is the info string described in the spec for the key or
+    /// `keys` MAC being calculated; `input` is the base64-encoded key value,
+    /// or the sorted, comma-joined list of key IDs for the `keys` MAC.
+    ///
+    /// All three [`MessageAuthenticationCode`] variants derive their MAC key
+    /// the same way; the only difference between them is the base64
+    /// encoding of the result (see below).
+    pub fn calculate_mac(&self, input: &[u8], info: &[u8]) -> String {
+        let mut mac_key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &self.shared_secret)
+            .expand(info, &mut mac_key)
+            .expect("HKDF output length is valid for SHA-256");
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&mac_key).expect("HMAC can take a key of any size");
+        mac.update(input);
+        let result = mac.finalize().into_bytes();
+
+        match self.mac_method {
+            // The `org.matrix.msc3783.hkdf-hmac-sha256` MAC encodes as unpadded base64.
+            #[cfg(feature = "unstable-msc3783")]
+            MessageAuthenticationCode::HkdfHmacSha256V2 => STANDARD_NO_PAD.encode(result),
+            _ => STANDARD.encode(result),
+        }
+    }
+
+    /// Verifies that `mac` is the correct MAC for `input`, as calculated by
+    /// [`Mac::calculate_mac`].
+    ///
+    /// The comparison is performed in constant time, and a dedicated
+    /// [`MacError::Mismatch`] is returned if the MACs don't match.
+    pub fn verify_mac(&self, input: &[u8], info: &[u8], mac: &str) -> Result<(), MacError> {
+        let calculated = self.calculate_mac(input, info);
+
+        let matches = calculated.len() == mac.len()
+            && calculated
+                .bytes()
+                .zip(mac.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+
+        if matches {
+            Ok(())
+        } else {
+            Err(MacError::Mismatch)
+        }
+    }
+}
+
+/// Builds the input for the `keys` MAC: the key IDs being verified, sorted
+/// and joined with commas, as described in the [spec].
+///
+/// [spec]: https://spec.matrix.org/v1.4/client-server-api/#mkeyverificationmac
+pub fn key_ids_input<'a>(key_ids: impl IntoIterator<Item = &'a str>) -> String {
+    let mut key_ids: Vec<&str> = key_ids.into_iter().collect();
+    key_ids.sort_unstable();
+    key_ids.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_ids_input, Mac};
+    use crate::events::key::verification::MessageAuthenticationCode;
+
+    #[test]
+    fn mac_round_trips() {
+        let mac = Mac::new(b"shared secret".to_vec(), MessageAuthenticationCode::HkdfHmacSha256);
+        let calculated = mac.calculate_mac(b"ed25519 key value", b"info string");
+
+        assert!(mac.verify_mac(b"ed25519 key value", b"info string", &calculated).is_ok());
+    }
+
+    #[test]
+    fn mac_mismatch_is_rejected() {
+        let mac = Mac::new(b"shared secret".to_vec(), MessageAuthenticationCode::HkdfHmacSha256);
+        let calculated = mac.calculate_mac(b"ed25519 key value", b"info string");
+        let tampered = format!("{}x", &calculated[..calculated.len() - 1]);
+
+        assert!(mac.verify_mac(b"ed25519 key value", b"info string", &tampered).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "unstable-msc3783")]
+    fn v2_mac_is_unpadded_base64() {
+        let mac =
+            Mac::new(b"shared secret".to_vec(), MessageAuthenticationCode::HkdfHmacSha256V2);
+        let calculated = mac.calculate_mac(b"ed25519 key value", b"info string");
+
+        assert!(!calculated.ends_with('='));
+    }
+
+    #[test]
+    fn legacy_hmac_sha256_derives_the_same_key_as_hkdf_hmac_sha256() {
+        // `HmacSha256` and `HkdfHmacSha256` only differ in name, not in how
+        // the MAC key is derived or the MAC encoded; both go through
+        // HKDF-SHA256 and padded base64.
+        let legacy = Mac::new(b"shared secret".to_vec(), MessageAuthenticationCode::HmacSha256);
+        let hkdf = Mac::new(b"shared secret".to_vec(), MessageAuthenticationCode::HkdfHmacSha256);
+
+        let legacy_mac = legacy.calculate_mac(b"ed25519 key value", b"info string");
+        let hkdf_mac = hkdf.calculate_mac(b"ed25519 key value", b"info string");
+
+        assert_eq!(legacy_mac, hkdf_mac);
+        assert!(legacy.verify_mac(b"ed25519 key value", b"info string", &hkdf_mac).is_ok());
+        assert!(hkdf.verify_mac(b"ed25519 key value", b"info string", &legacy_mac).is_ok());
+    }
+
+    #[test]
+    fn keys_input_is_sorted_and_joined() {
+        let input = key_ids_input(["ed25519:DEVICEID", "ed25519:ABCDEFG"]);
+        assert_eq!(input, "ed25519:ABCDEFG,ed25519:DEVICEID");
+    }
+}
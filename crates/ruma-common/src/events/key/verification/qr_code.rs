@@ -0,0 +1,327 @@
+//! Types for encoding and decoding the binary payload embedded in QR codes
+//! used by the `m.qr_code.scan.v1` and `m.qr_code.show.v1` verification
+//! methods, as described in the [spec].
+//!
+//! [spec]: https://spec.matrix.org/v1.4/client-server-api/#qr-code-format
+
+/// The ASCII prefix that every QR verification payload starts with.
+const PREFIX: &[u8] = b"MATRIX";
+
+/// The only version of the QR verification payload format that is
+/// understood.
+const VERSION: u8 = 0x02;
+
+/// The length, in bytes, of each of the two public keys embedded in the
+/// payload.
+const KEY_LENGTH: usize = 32;
+
+
+/// The decoded payload of a QR code used for key verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrVerificationData {
+    mode: QrVerificationMode,
+    flow_id: String,
+    first_key: [u8; KEY_LENGTH],
+    second_key: [u8; KEY_LENGTH],
+    shared_secret: Vec<u8>,
+}
+
+impl QrVerificationData {
+    /// Creates a new `QrVerificationData` with the given mode, transaction
+    /// ID (or flow event ID), public keys and shared secret.
+    pub fn new(
+        mode: QrVerificationMode,
+        flow_id: String,
+        first_key: [u8; KEY_LENGTH],
+        second_key: [u8; KEY_LENGTH],
+        shared_secret: Vec<u8>,
+    ) -> Self {
+        Self { mode, flow_id, first_key, second_key, shared_secret }
+    }
+
+    /// The verification mode this payload was generated for.
+    pub fn mode(&self) -> QrVerificationMode {
+        self.mode
+    }
+
+    /// The transaction ID, or the event ID of the `m.key.verification.ready`
+    /// event that started the flow.
+    pub fn flow_id(&self) -> &str {
+        &self.flow_id
+    }
+
+    /// The first public key.
+    ///
+    /// For [`QrVerificationMode::Verification`] this is the scanning user's
+    /// own master cross-signing key; for the self-verification modes it is
+    /// the current device's device key.
+    pub fn first_key(&self) -> &[u8; KEY_LENGTH] {
+        &self.first_key
+    }
+
+    /// The second public key.
+    ///
+    /// For [`QrVerificationMode::Verification`] this is the other user's
+    /// master cross-signing key; for the self-verification modes it is the
+    /// master cross-signing key of the account being verified.
+    pub fn second_key(&self) -> &[u8; KEY_LENGTH] {
+        &self.second_key
+    }
+
+    /// The shared secret, used to verify the other party over the
+    /// `m.key.verification.reciprocate` in-band exchange.
+    pub fn shared_secret(&self) -> &[u8] {
+        &self.shared_secret
+    }
+
+    /// Encodes this payload into the binary format that a client would
+    /// render as a QR code.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let flow_id = self.flow_id.as_bytes();
+
+        let mut bytes = Vec::with_capacity(
+            PREFIX.len() + 1 + 1 + 2 + flow_id.len() + 2 * KEY_LENGTH + self.shared_secret.len(),
+        );
+
+        bytes.extend_from_slice(PREFIX);
+        bytes.push(VERSION);
+        bytes.push(self.mode.into());
+        bytes.extend_from_slice(&(flow_id.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(flow_id);
+        bytes.extend_from_slice(&self.first_key);
+        bytes.extend_from_slice(&self.second_key);
+        bytes.extend_from_slice(&self.shared_secret);
+
+        bytes
+    }
+
+    /// Decodes a `QrVerificationData` from the binary payload embedded in a
+    /// scanned QR code.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, QrVerificationDataError> {
+        let rest = bytes
+            .strip_prefix(PREFIX)
+            .ok_or(QrVerificationDataError::InvalidPrefix)?;
+
+        let (&version, rest) = rest.split_first().ok_or(QrVerificationDataError::UnexpectedEnd)?;
+        if version != VERSION {
+            return Err(QrVerificationDataError::UnsupportedVersion(version));
+        }
+
+        let (&mode, rest) = rest.split_first().ok_or(QrVerificationDataError::UnexpectedEnd)?;
+        let mode = QrVerificationMode::try_from(mode)?;
+
+        if rest.len() < 2 {
+            return Err(QrVerificationDataError::UnexpectedEnd);
+        }
+        let (flow_id_len, rest) = rest.split_at(2);
+        let flow_id_len = u16::from_be_bytes([flow_id_len[0], flow_id_len[1]]) as usize;
+
+        if rest.len() < flow_id_len + 2 * KEY_LENGTH {
+            return Err(QrVerificationDataError::UnexpectedEnd);
+        }
+
+        let (flow_id, rest) = rest.split_at(flow_id_len);
+        let flow_id = String::from_utf8(flow_id.to_vec())
+            .map_err(|_| QrVerificationDataError::InvalidFlowId)?;
+
+        let (first_key, rest) = rest.split_at(KEY_LENGTH);
+        let (second_key, shared_secret) = rest.split_at(KEY_LENGTH);
+
+        // The spec defines the shared secret as "the remainder of the
+        // buffer", with no minimum length of its own, so there's no wire
+        // format signal to tell a legitimately short secret apart from a
+        // truncated one; an empty secret, however, can never be valid,
+        // since it wouldn't be usable to verify anything. Callers that know
+        // the minimum length their key-agreement protocol produces should
+        // validate `shared_secret()` themselves.
+        if shared_secret.is_empty() {
+            return Err(QrVerificationDataError::SharedSecretTooShort);
+        }
+
+        let mut first_key_bytes = [0; KEY_LENGTH];
+        first_key_bytes.copy_from_slice(first_key);
+        let mut second_key_bytes = [0; KEY_LENGTH];
+        second_key_bytes.copy_from_slice(second_key);
+
+        Ok(Self {
+            mode,
+            flow_id,
+            first_key: first_key_bytes,
+            second_key: second_key_bytes,
+            shared_secret: shared_secret.to_vec(),
+        })
+    }
+}
+
+/// The three QR code verification modes described in the [spec].
+///
+/// [spec]: https://spec.matrix.org/v1.4/client-server-api/#qr-code-format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QrVerificationMode {
+    /// `0x00`: Verifying another user's device.
+    Verification,
+
+    /// `0x01`: Self-verifying in which the current device already trusts
+    /// (has cross-signed) the master key.
+    SelfVerification,
+
+    /// `0x02`: Self-verifying in which the current device does not yet
+    /// trust the master key.
+    SelfVerificationNoTrust,
+}
+
+impl From<QrVerificationMode> for u8 {
+    fn from(mode: QrVerificationMode) -> Self {
+        match mode {
+            QrVerificationMode::Verification => 0x00,
+            QrVerificationMode::SelfVerification => 0x01,
+            QrVerificationMode::SelfVerificationNoTrust => 0x02,
+        }
+    }
+}
+
+impl TryFrom<u8> for QrVerificationMode {
+    type Error = QrVerificationDataError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0x00 => Ok(Self::Verification),
+            0x01 => Ok(Self::SelfVerification),
+            0x02 => Ok(Self::SelfVerificationNoTrust),
+            _ => Err(QrVerificationDataError::UnknownMode(byte)),
+        }
+    }
+}
+
+/// An error encountered while decoding a [`QrVerificationData`] payload.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QrVerificationDataError {
+    /// The payload didn't start with the `MATRIX` ASCII prefix.
+    #[error("payload is missing the `MATRIX` prefix")]
+    InvalidPrefix,
+
+    /// The payload declared a version other than the one this crate
+    /// understands.
+    #[error("unsupported QR verification payload version: {0}")]
+    UnsupportedVersion(u8),
+
+    /// The payload declared a mode byte that isn't one of the three known
+    /// modes.
+    #[error("unknown QR verification mode: {0}")]
+    UnknownMode(u8),
+
+    /// The transaction ID (or flow event ID) was not valid UTF-8.
+    #[error("flow ID is not valid UTF-8")]
+    InvalidFlowId,
+
+    /// The payload was shorter than the fields it declared.
+    #[error("QR verification payload is truncated")]
+    UnexpectedEnd,
+
+    /// The shared secret remaining after the fixed-size fields is empty.
+    #[error("QR verification shared secret is too short")]
+    SharedSecretTooShort,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QrVerificationData, QrVerificationDataError, QrVerificationMode};
+
+    fn sample() -> QrVerificationData {
+        QrVerificationData::new(
+            QrVerificationMode::Verification,
+            "abcdefg".to_owned(),
+            [1; 32],
+            [2; 32],
+            vec![3; 16],
+        )
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let data = sample();
+        let bytes = data.to_bytes();
+        let decoded = QrVerificationData::from_bytes(&bytes).unwrap();
+
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn rejects_wrong_prefix() {
+        let bytes = b"NOTMATRIX".to_vec();
+        assert_eq!(
+            QrVerificationData::from_bytes(&bytes).unwrap_err(),
+            QrVerificationDataError::InvalidPrefix
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let mut bytes = sample().to_bytes();
+        bytes[6] = 0x01;
+
+        assert_eq!(
+            QrVerificationData::from_bytes(&bytes).unwrap_err(),
+            QrVerificationDataError::UnsupportedVersion(0x01)
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_buffer() {
+        let bytes = sample().to_bytes();
+        // Cut into the fixed-size keys (dropping the whole shared secret and
+        // then some), not just the variable-length shared secret tail.
+        let truncated = &bytes[..bytes.len() - sample().shared_secret().len() - 1];
+
+        assert_eq!(
+            QrVerificationData::from_bytes(truncated).unwrap_err(),
+            QrVerificationDataError::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn rejects_empty_shared_secret() {
+        let data = QrVerificationData::new(
+            QrVerificationMode::Verification,
+            "abcdefg".to_owned(),
+            [1; 32],
+            [2; 32],
+            vec![3; 1],
+        );
+        let bytes = data.to_bytes();
+        // Drop the single byte of shared secret; the keys and flow ID are
+        // still fully present.
+        let truncated = &bytes[..bytes.len() - 1];
+
+        assert_eq!(
+            QrVerificationData::from_bytes(truncated).unwrap_err(),
+            QrVerificationDataError::SharedSecretTooShort
+        );
+    }
+
+    #[test]
+    fn accepts_a_single_byte_shared_secret() {
+        let data = QrVerificationData::new(
+            QrVerificationMode::Verification,
+            "abcdefg".to_owned(),
+            [1; 32],
+            [2; 32],
+            vec![3; 1],
+        );
+        let bytes = data.to_bytes();
+
+        assert_eq!(QrVerificationData::from_bytes(&bytes).unwrap(), data);
+    }
+
+    #[test]
+    fn mode_byte_round_trips() {
+        for mode in [
+            QrVerificationMode::Verification,
+            QrVerificationMode::SelfVerification,
+            QrVerificationMode::SelfVerificationNoTrust,
+        ] {
+            let byte: u8 = mode.into();
+            assert_eq!(QrVerificationMode::try_from(byte).unwrap(), mode);
+        }
+    }
+}
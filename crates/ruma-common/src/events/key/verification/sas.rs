@@ -0,0 +1,198 @@
+//! Types and helpers for the `m.sas.v1` short authentication string method.
+//!
+//! These helpers turn the raw bytes produced by the HKDF-SHA256 key
+//! derivation of the agreed shared secret into the decimal numbers or emoji
+//! that are shown to the user for comparison, as described in the [spec].
+//!
+//! [spec]: https://spec.matrix.org/v1.4/client-server-api/#sas-method-decimal
+
+/// The number of bytes of HKDF-SHA256 output needed to compute both the
+/// decimal (5 bytes) and emoji (6 bytes) short authentication strings.
+const SAS_BYTES_LENGTH: usize = 6;
+
+/// The bytes generated by the HKDF-SHA256 key derivation of the shared
+/// secret agreed upon during SAS verification.
+///
+/// This is a fixed-size array rather than a `Vec<u8>` so that a buffer of
+/// the wrong size is a compile error for callers, instead of a panic in
+/// [`SasBytes::decimals`]/[`SasBytes::emoji_indices`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SasBytes {
+    bytes: [u8; SAS_BYTES_LENGTH],
+}
+
+impl SasBytes {
+    /// Creates a new `SasBytes` from the first 6 bytes of the raw
+    /// HKDF-SHA256 output of the agreed shared secret.
+    ///
+    /// 6 bytes are enough to compute both the emoji short authentication
+    /// string, which needs 6, and the decimal one, which needs 5.
+    pub fn new(bytes: [u8; SAS_BYTES_LENGTH]) -> Self {
+        Self { bytes }
+    }
+
+    /// Generate the three decimal numbers that make up the decimal short
+    /// authentication string, as described in the [spec].
+    ///
+    /// Each number is in the range 1000–9191, inclusive.
+    ///
+    /// [spec]: https://spec.matrix.org/v1.4/client-server-api/#sas-method-decimal
+    pub fn decimals(&self) -> (u16, u16, u16) {
+        (
+            bits_at(&self.bytes, 0, 13) as u16 + 1000,
+            bits_at(&self.bytes, 13, 13) as u16 + 1000,
+            bits_at(&self.bytes, 26, 13) as u16 + 1000,
+        )
+    }
+
+    /// Generate the indices of the seven emoji that make up the emoji short
+    /// authentication string, as described in the [spec].
+    ///
+    /// Each index is in the range 0–63, inclusive, and can be used to look
+    /// up the emoji and its name in [`EMOJI`].
+    ///
+    /// [spec]: https://spec.matrix.org/v1.4/client-server-api/#sas-method-emoji
+    pub fn emoji_indices(&self) -> [u8; 7] {
+        let mut indices = [0; 7];
+
+        for (i, index) in indices.iter_mut().enumerate() {
+            *index = bits_at(&self.bytes, i as u32 * 6, 6) as u8;
+        }
+
+        indices
+    }
+
+    /// Generate the seven emoji, together with their English names, that
+    /// make up the emoji short authentication string.
+    pub fn emoji(&self) -> [(&'static str, &'static str); 7] {
+        self.emoji_indices().map(|index| EMOJI[index as usize])
+    }
+}
+
+/// Extract `len` bits (at most 32) from `bytes`, starting at bit `start`
+/// counted from the most significant bit of the first byte.
+fn bits_at(bytes: &[u8], start: u32, len: u32) -> u32 {
+    let mut result = 0;
+
+    for i in 0..len {
+        let bit_pos = start + i;
+        let byte = bytes[(bit_pos / 8) as usize];
+        let bit = (byte >> (7 - (bit_pos % 8))) & 1;
+        result = (result << 1) | u32::from(bit);
+    }
+
+    result
+}
+
+/// The 64-entry emoji table used for the emoji short authentication string
+/// method, as defined by the [spec].
+///
+/// The ordering of this table is normative: the `n`th entry corresponds to
+/// the emoji index `n` produced by [`SasBytes::emoji_indices`].
+///
+/// [spec]: https://spec.matrix.org/v1.4/client-server-api/#sas-method-emoji
+pub const EMOJI: [(&str, &str); 64] = [
+    ("🐶", "Dog"),
+    ("🐱", "Cat"),
+    ("🦁", "Lion"),
+    ("🐎", "Horse"),
+    ("🦄", "Unicorn"),
+    ("🐷", "Pig"),
+    ("🐘", "Elephant"),
+    ("🐰", "Rabbit"),
+    ("🐼", "Panda"),
+    ("🐓", "Rooster"),
+    ("🐧", "Penguin"),
+    ("🐢", "Turtle"),
+    ("🐟", "Fish"),
+    ("🐙", "Octopus"),
+    ("🦋", "Butterfly"),
+    ("🌷", "Flower"),
+    ("🌳", "Tree"),
+    ("🌵", "Cactus"),
+    ("🍄", "Mushroom"),
+    ("🌏", "Globe"),
+    ("🌙", "Moon"),
+    ("☁️", "Cloud"),
+    ("🔥", "Fire"),
+    ("🍌", "Banana"),
+    ("🍎", "Apple"),
+    ("🍓", "Strawberry"),
+    ("🌽", "Corn"),
+    ("🍕", "Pizza"),
+    ("🎂", "Cake"),
+    ("❤️", "Heart"),
+    ("😀", "Smiley"),
+    ("🤖", "Robot"),
+    ("🎩", "Hat"),
+    ("👓", "Glasses"),
+    ("🔧", "Spanner"),
+    ("🎅", "Santa"),
+    ("👍", "Thumbs Up"),
+    ("☂️", "Umbrella"),
+    ("⌛", "Hourglass"),
+    ("⏰", "Clock"),
+    ("🎁", "Gift"),
+    ("💡", "Light Bulb"),
+    ("📕", "Book"),
+    ("✏️", "Pencil"),
+    ("📎", "Paperclip"),
+    ("✂️", "Scissors"),
+    ("🔒", "Lock"),
+    ("🔑", "Key"),
+    ("🔨", "Hammer"),
+    ("☎️", "Telephone"),
+    ("🏁", "Flag"),
+    ("🚂", "Train"),
+    ("🚲", "Bicycle"),
+    ("✈️", "Airplane"),
+    ("🚀", "Rocket"),
+    ("🏆", "Trophy"),
+    ("⚽", "Ball"),
+    ("🎸", "Guitar"),
+    ("🎺", "Trumpet"),
+    ("🔔", "Bell"),
+    ("⚓", "Anchor"),
+    ("🎧", "Headphones"),
+    ("📁", "Folder"),
+    ("📌", "Pin"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::SasBytes;
+
+    #[test]
+    fn decimals_and_emoji_for_all_zero_bytes() {
+        let sas = SasBytes::new([0; 6]);
+
+        assert_eq!(sas.decimals(), (1000, 1000, 1000));
+        assert_eq!(sas.emoji_indices(), [0; 7]);
+    }
+
+    #[test]
+    fn decimals_and_emoji_for_all_one_bytes() {
+        let sas = SasBytes::new([0xFF; 6]);
+
+        assert_eq!(sas.decimals(), (9191, 9191, 9191));
+        assert_eq!(sas.emoji_indices(), [63; 7]);
+    }
+
+    #[test]
+    fn decimals_and_emoji_for_mixed_bytes() {
+        let sas =
+            SasBytes::new([0b1011_0100, 0b0010_1101, 0b1101_0010, 0b0101_1011, 0b0010_0110, 0b1100_1000]);
+
+        assert_eq!(sas.decimals(), (6765, 6961, 4475));
+        assert_eq!(sas.emoji_indices(), [45, 2, 55, 18, 22, 50, 27]);
+    }
+
+    #[test]
+    fn emoji_table_matches_indices() {
+        let sas = SasBytes::new([0b1011_0100, 0b0010_1101, 0b1101_0010, 0b0101_1011, 0b0010_0110, 0b1100_1000]);
+
+        let emoji = sas.emoji();
+        assert_eq!(emoji[0], super::EMOJI[45]);
+        assert_eq!(emoji[6], super::EMOJI[27]);
+    }
+}